@@ -1,10 +1,10 @@
 use wee_server::{Request, Response, Server};
 
-fn main() {
-    Server::bind("0.0.0.0:8080").path("/", root).listen()
+fn main() -> std::io::Result<()> {
+    Server::bind("0.0.0.0:8080")?.path("/", root).listen();
+    Ok(())
 }
 
-fn root(req: Request) -> Response {
-    let res = Response::new();
-    res
+fn root(_req: &Request) -> Response {
+    Response::new()
 }
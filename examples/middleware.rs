@@ -0,0 +1,27 @@
+use std::time::Instant;
+use wee_server::{Method, Next, Request, Response, Server};
+
+fn main() -> std::io::Result<()> {
+    Server::bind("0.0.0.0:8080")?
+        .wrap(logger)
+        .route(Method::Get, "/", root)
+        .listen();
+    Ok(())
+}
+
+fn logger(request: &Request, next: Next) -> Response {
+    let start = Instant::now();
+    let response = next.run(request);
+    println!(
+        "{} {} {} {:?}",
+        request.method(),
+        request.path(),
+        response.status_code(),
+        start.elapsed()
+    );
+    response
+}
+
+fn root(_req: &Request) -> Response {
+    Response::new().set_body("Hello from behind the middleware")
+}
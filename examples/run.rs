@@ -0,0 +1,11 @@
+use wee_server::{Request, Response, Server};
+
+fn main() -> std::io::Result<()> {
+    let addr = std::env::args().nth(1).unwrap_or("127.0.0.1:8080".into());
+    Server::bind(addr)?.run(echo_path);
+    Ok(())
+}
+
+fn echo_path(request: &Request) -> Response {
+    Response::text(request.path())
+}
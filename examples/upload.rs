@@ -0,0 +1,25 @@
+use std::io::Read;
+use wee_server::{Method, Request, Response, Server, StatusCode};
+
+fn main() -> std::io::Result<()> {
+    Server::bind("0.0.0.0:8080")?
+        .stream(Method::Post, "/upload", upload)
+        .listen();
+    Ok(())
+}
+
+fn upload(_req: &Request, body: &mut dyn Read) -> Response {
+    let mut chunk = [0u8; 8192];
+    let mut total = 0;
+    loop {
+        match body.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(len) => total += len,
+            Err(err) => {
+                return Response::text(err)
+                    .set_status_code(StatusCode::BadRequest)
+            }
+        }
+    }
+    Response::text(format!("received {total} bytes"))
+}
@@ -0,0 +1,53 @@
+pub(crate) fn quality(accept: &str, media_type: &str) -> f32 {
+    let (kind, subtype) =
+        media_type.split_once('/').unwrap_or((media_type, ""));
+    let mut best: Option<(u8, f32)> = None;
+    for range in accept.split(',') {
+        let mut params = range.split(';');
+        let range = params.next().unwrap_or_default().trim();
+        let Some((range_kind, range_subtype)) = range.split_once('/') else {
+            continue;
+        };
+        let specificity = match (range_kind, range_subtype) {
+            ("*", "*") => 0,
+            (range_kind, "*") if range_kind.eq_ignore_ascii_case(kind) => 1,
+            (range_kind, range_subtype)
+                if range_kind.eq_ignore_ascii_case(kind)
+                    && range_subtype.eq_ignore_ascii_case(subtype) =>
+            {
+                2
+            }
+            _ => continue,
+        };
+        let q = q_value(params);
+        if best.is_none_or(|(best, _)| specificity > best) {
+            best = Some((specificity, q));
+        }
+    }
+    best.map_or(0.0, |(_, q)| q)
+}
+
+pub(crate) fn q_value<'a>(params: impl Iterator<Item = &'a str>) -> f32 {
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+        .and_then(|(_, value)| value.trim().parse::<f32>().ok())
+        .map_or(1.0, |q| q.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quality_values() {
+        let accept =
+            "text/html, application/json;q=0.9, text/*;q=0.5, */*;q=0.1";
+        assert_eq!(quality(accept, "text/html"), 1.0);
+        assert_eq!(quality(accept, "application/json"), 0.9);
+        assert_eq!(quality(accept, "text/plain"), 0.5);
+        assert_eq!(quality(accept, "image/png"), 0.1);
+        assert_eq!(quality("text/html", "image/png"), 0.0);
+        assert_eq!(quality("text/html;q=0", "text/html"), 0.0);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    date,
+    http::{Request, Response},
+};
+use std::{
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "log")]
+use log::info;
+
+pub(crate) struct Entry {
+    peer: Option<SocketAddr>,
+    request_line: Option<String>,
+}
+
+impl Entry {
+    pub(crate) fn new(
+        peer: Option<SocketAddr>,
+        request: Option<&Request>,
+    ) -> Self {
+        Self {
+            peer,
+            request_line: request.map(|request| {
+                format!(
+                    "{} {} {}",
+                    request.method(),
+                    request.target(),
+                    <&str>::from(*request.protocol())
+                )
+            }),
+        }
+    }
+
+    pub(crate) fn log(&self, response: &Response, elapsed: Duration) {
+        let line = self.format(response, elapsed, SystemTime::now());
+        #[cfg(feature = "log")]
+        info!("{line}");
+        #[cfg(not(feature = "log"))]
+        println!("{line}");
+    }
+
+    // Common Log Format, with the response time in microseconds appended
+    fn format(
+        &self,
+        response: &Response,
+        elapsed: Duration,
+        now: SystemTime,
+    ) -> String {
+        format!(
+            "{} - - [{}] \"{}\" {} {} {}",
+            self.peer.map_or("-".into(), |peer| peer.ip().to_string()),
+            date::clf_date(now),
+            self.request_line.as_deref().unwrap_or("-"),
+            response.status_code().code(),
+            match response.body_len() {
+                0 => "-".into(),
+                len => len.to_string(),
+            },
+            elapsed.as_micros(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn common_log_format() {
+        let request =
+            Request::from_bytes(b"GET /index.html?a=1 HTTP/1.1\r\n\r\n")
+                .unwrap();
+        let entry =
+            Entry::new(Some("127.0.0.1:4000".parse().unwrap()), Some(&request));
+        let line = entry.format(
+            &Response::text("hello"),
+            Duration::from_micros(1500),
+            UNIX_EPOCH + Duration::from_secs(784111777),
+        );
+        assert_eq!(
+            line,
+            "127.0.0.1 - - [06/Nov/1994:08:49:37 +0000] \"GET /index.html?a=1 HTTP/1.1\" 200 5 1500"
+        );
+
+        let line = Entry::new(None, None).format(
+            &Response::new(),
+            Duration::ZERO,
+            UNIX_EPOCH,
+        );
+        assert_eq!(line, "- - - [01/Jan/1970:00:00:00 +0000] \"-\" 200 - 0");
+    }
+}
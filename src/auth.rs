@@ -0,0 +1,29 @@
+use crate::base64;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Auth {
+    Basic { username: String, password: String },
+    Bearer(String),
+}
+
+impl Auth {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let (scheme, credentials) = value.trim().split_once(' ')?;
+        let credentials = credentials.trim();
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded =
+                String::from_utf8(base64::decode(credentials)?).ok()?;
+            let (username, password) = decoded.split_once(':')?;
+            Some(Self::Basic {
+                username: username.into(),
+                password: password.into(),
+            })
+        } else if scheme.eq_ignore_ascii_case("bearer")
+            && !credentials.is_empty()
+        {
+            Some(Self::Bearer(credentials.into()))
+        } else {
+            None
+        }
+    }
+}
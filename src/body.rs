@@ -0,0 +1,315 @@
+use crate::http::{Error, Limits, Request};
+use std::io::{self, Read};
+
+enum State {
+    Length(usize),
+    ChunkSize,
+    ChunkData(usize),
+    ChunkEnd,
+    Trailers,
+    Done,
+}
+
+pub(crate) struct BodyReader<'a, R> {
+    buf: &'a mut Vec<u8>,
+    reader: &'a mut R,
+    state: State,
+    max_body_bytes: usize,
+    max_line_bytes: usize,
+    read: usize,
+    trailer_bytes: usize,
+    trailers: Vec<(String, String)>,
+}
+
+impl<'a, R: Read> BodyReader<'a, R> {
+    pub(crate) fn new(
+        request: &Request,
+        buf: &'a mut Vec<u8>,
+        reader: &'a mut R,
+        limits: &Limits,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            buf,
+            reader,
+            state: initial_state(request, limits)?,
+            max_body_bytes: limits.max_body_bytes,
+            max_line_bytes: limits.max_header_bytes,
+            read: 0,
+            trailer_bytes: 0,
+            trailers: Vec::new(),
+        })
+    }
+
+    pub(crate) fn take_trailers(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.trailers)
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; 2048];
+        let len = self.reader.read(&mut chunk)?;
+        if len == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.buf.extend_from_slice(&chunk[..len]);
+        Ok(())
+    }
+
+    fn read_line(
+        &mut self,
+        max_len: usize,
+        too_long: Error,
+    ) -> io::Result<String> {
+        loop {
+            if let Some(end) =
+                self.buf.windows(2).position(|window| window == b"\r\n")
+            {
+                if end > max_len {
+                    return Err(invalid(too_long));
+                }
+                let line = String::from_utf8(self.buf[..end].to_vec())
+                    .map_err(|_| invalid(Error::InvalidChunkedBody))?;
+                self.buf.drain(..end + 2);
+                return Ok(line);
+            }
+            if self.buf.len() > max_len + 1 {
+                return Err(invalid(too_long));
+            }
+            self.fill()?;
+        }
+    }
+
+    fn read_data(
+        &mut self,
+        out: &mut [u8],
+        remaining: usize,
+    ) -> io::Result<usize> {
+        if self.buf.is_empty() {
+            self.fill()?;
+        }
+        let len = out.len().min(remaining).min(self.buf.len());
+        out[..len].copy_from_slice(&self.buf[..len]);
+        self.buf.drain(..len);
+        Ok(len)
+    }
+}
+
+impl<R: Read> Read for BodyReader<'_, R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match self.state {
+                State::Done => return Ok(0),
+                State::Length(0) => self.state = State::Done,
+                State::Length(remaining) => {
+                    let len = self.read_data(out, remaining)?;
+                    self.state = State::Length(remaining - len);
+                    return Ok(len);
+                }
+                State::ChunkSize => {
+                    let line = self.read_line(
+                        self.max_line_bytes,
+                        Error::InvalidChunkedBody,
+                    )?;
+                    let size = line.split(';').next().unwrap_or_default();
+                    let size = usize::from_str_radix(size.trim(), 16)
+                        .map_err(|_| invalid(Error::InvalidChunkedBody))?;
+                    if self.read.saturating_add(size) > self.max_body_bytes {
+                        return Err(invalid(Error::BodyTooLarge));
+                    }
+                    self.state = match size {
+                        0 => State::Trailers,
+                        size => State::ChunkData(size),
+                    };
+                }
+                State::ChunkData(remaining) => {
+                    let len = self.read_data(out, remaining)?;
+                    self.read += len;
+                    self.state = match remaining - len {
+                        0 => State::ChunkEnd,
+                        remaining => State::ChunkData(remaining),
+                    };
+                    return Ok(len);
+                }
+                State::ChunkEnd => {
+                    if !self.read_line(0, Error::InvalidChunkedBody)?.is_empty()
+                    {
+                        return Err(invalid(Error::InvalidChunkedBody));
+                    }
+                    self.state = State::ChunkSize;
+                }
+                State::Trailers => {
+                    let remaining =
+                        self.max_line_bytes.saturating_sub(self.trailer_bytes);
+                    let line =
+                        self.read_line(remaining, Error::HeadersTooLarge)?;
+                    self.trailer_bytes += line.len() + 2;
+                    if line.is_empty() {
+                        self.state = State::Done;
+                        continue;
+                    }
+                    let Some((key, value)) = line.split_once(':') else {
+                        return Err(invalid(Error::InvalidHeader(line)));
+                    };
+                    self.trailers.push((
+                        key.trim().to_ascii_lowercase(),
+                        value.trim().into(),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn initial_state(request: &Request, limits: &Limits) -> Result<State, Error> {
+    match (request.is_chunked(), request.header("content-length")) {
+        (true, _) => Ok(State::ChunkSize),
+        (false, Some(len)) => {
+            let len = len.parse().map_err(|_| Error::InvalidContentLength)?;
+            if len > limits.max_body_bytes {
+                return Err(Error::BodyTooLarge);
+            }
+            Ok(State::Length(len))
+        }
+        (false, None) => Ok(State::Done),
+    }
+}
+
+// Lets the server decide whether to invite a body with `100 Continue`
+// before any of it is read.
+pub(crate) fn accepts_body(
+    request: &Request,
+    limits: &Limits,
+) -> Result<(), Error> {
+    initial_state(request, limits).map(drop)
+}
+
+fn invalid(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+pub(crate) fn into_error(err: io::Error) -> Option<Error> {
+    err.into_inner()?.downcast::<Error>().ok().map(|err| *err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Limits;
+
+    struct Outcome {
+        body: Vec<u8>,
+        leftover: Vec<u8>,
+        trailers: Vec<(String, String)>,
+        result: Result<usize, Option<Error>>,
+    }
+
+    fn read_body(head: &str, rest: &[u8]) -> Outcome {
+        let (request, _) =
+            Request::parse_head_prefix(head.as_bytes(), &Limits::default())
+                .unwrap()
+                .unwrap();
+        let (mut buf, mut reader) = (rest[..3].to_vec(), &rest[3..]);
+        let mut body = Vec::new();
+        let limits = Limits::new().max_body_bytes(64).max_header_bytes(32);
+        let mut body_reader =
+            BodyReader::new(&request, &mut buf, &mut reader, &limits).unwrap();
+        let result = body_reader.read_to_end(&mut body).map_err(into_error);
+        let trailers = body_reader.take_trailers();
+        buf.extend_from_slice(reader);
+        Outcome {
+            body,
+            leftover: buf,
+            trailers,
+            result,
+        }
+    }
+
+    #[test]
+    fn content_length_body() {
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\n",
+            b"helloGET / HTTP/1.1\r\n\r\n",
+        );
+        assert!(outcome.result.is_ok());
+        assert_eq!(outcome.body, b"hello");
+        assert_eq!(outcome.leftover, b"GET / HTTP/1.1\r\n\r\n");
+    }
+
+    #[test]
+    fn chunked_body_with_trailers() {
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"5;x=1\r\nhello\r\n7\r\n, world\r\n0\r\nX-Checksum: abc\r\n\r\nnext",
+        );
+        assert!(outcome.result.is_ok());
+        assert_eq!(outcome.body, b"hello, world");
+        assert_eq!(outcome.trailers, [("x-checksum".into(), "abc".into())]);
+        assert_eq!(outcome.leftover, b"next");
+    }
+
+    #[test]
+    fn body_errors() {
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"zz\r\nhello\r\n",
+        );
+        assert!(matches!(
+            outcome.result,
+            Err(Some(Error::InvalidChunkedBody))
+        ));
+
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"41\r\n",
+        );
+        assert!(matches!(outcome.result, Err(Some(Error::BodyTooLarge))));
+
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n",
+            b"short",
+        );
+        assert!(matches!(outcome.result, Err(None)));
+
+        let (request, _) = Request::parse_head_prefix(
+            b"POST / HTTP/1.1\r\nContent-Length: 65\r\n\r\n",
+            &Limits::default(),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(matches!(
+            BodyReader::new(
+                &request,
+                &mut Vec::new(),
+                &mut &b""[..],
+                &Limits::new().max_body_bytes(64)
+            ),
+            Err(Error::BodyTooLarge)
+        ));
+    }
+
+    #[test]
+    fn unbounded_lines() {
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            &[b'0'; 256],
+        );
+        assert!(matches!(
+            outcome.result,
+            Err(Some(Error::InvalidChunkedBody))
+        ));
+
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"0\r\nX-Long: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        assert!(matches!(outcome.result, Err(Some(Error::HeadersTooLarge))));
+
+        let outcome = read_body(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"0\r\nA: 1\r\nB: 2\r\nC: 3\r\nD: 4\r\nE: 5\r\nF: 6\r\nG: 7\r\n\r\n",
+        );
+        assert!(matches!(outcome.result, Err(Some(Error::HeadersTooLarge))));
+    }
+}
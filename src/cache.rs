@@ -0,0 +1,228 @@
+use crate::{
+    http::{Method, Request, Response, StatusCode},
+    middleware::Next,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+type Key = (Method, Option<String>, String);
+
+pub struct Cache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<Key, (Instant, Response)>>,
+}
+
+impl Cache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: 1024,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn middleware(
+        self,
+    ) -> impl Fn(&Request, Next) -> Response + Send + Sync + 'static {
+        let cache = Arc::new(self);
+        move |request, next| cache.handle(request, next)
+    }
+
+    fn handle(&self, request: &Request, next: Next) -> Response {
+        let cacheable = matches!(request.method(), Method::Get | Method::Head)
+            && request.query().is_none_or(|query| query.is_empty())
+            && request.header("authorization").is_none()
+            && request.header("cookie").is_none();
+        if !cacheable {
+            return next.run(request);
+        }
+
+        let key = (
+            request.method().clone(),
+            request.host_header().map(str::to_ascii_lowercase),
+            request.path().to_string(),
+        );
+        if let Some(response) = self.get(&key) {
+            return response;
+        }
+
+        let response = next.run(request);
+        if is_shareable(&response) {
+            self.insert(key, response.clone());
+        }
+        response
+    }
+
+    fn insert(&self, key: Key, response: Response) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        while entries.len() >= self.max_entries {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        entries.insert(key, (Instant::now(), response));
+    }
+
+    fn get(&self, key: &Key) -> Option<Response> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some((stored, response)) if stored.elapsed() < self.ttl => {
+                Some(response.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+fn is_shareable(response: &Response) -> bool {
+    let private = response.header("cache-control").is_some_and(|value| {
+        value.split(',').any(|directive| {
+            let directive = directive.trim();
+            directive.eq_ignore_ascii_case("no-store")
+                || directive.eq_ignore_ascii_case("private")
+        })
+    });
+    response.status_code() == StatusCode::Ok
+        && !response.is_streamed()
+        && !private
+        && response.header("set-cookie").is_none()
+        && response.header("vary").is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::Middleware;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const NO_STORE: Option<(&str, &str)> = Some(("Cache-Control", "no-store"));
+
+    fn run(
+        middleware: &[Middleware],
+        calls: &AtomicUsize,
+        raw: &str,
+        header: Option<(&str, &str)>,
+    ) -> Response {
+        let request = Request::from_bytes(raw.as_bytes()).unwrap();
+        Next::new(middleware, &|_| {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            let response = Response::text(n);
+            match header {
+                Some((key, value)) => response.add_header(key, value),
+                None => response,
+            }
+        })
+        .run(&request)
+    }
+
+    #[test]
+    fn cache_get_responses() {
+        let middleware: Vec<Middleware> =
+            vec![Box::new(Cache::new(Duration::from_secs(60)).middleware())];
+        let calls = AtomicUsize::new(0);
+        let get = "GET /a HTTP/1.1\r\n\r\n";
+
+        let first = run(&middleware, &calls, get, None).serialise();
+        let second = run(&middleware, &calls, get, None).serialise();
+        assert_eq!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        run(&middleware, &calls, "GET /b HTTP/1.1\r\n\r\n", None);
+        run(&middleware, &calls, "POST /a HTTP/1.1\r\n\r\n", None);
+        run(&middleware, &calls, "GET /a?x=1 HTTP/1.1\r\n\r\n", None);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        let get = "GET /a HTTP/1.1\r\nHost: one.example\r\n\r\n";
+        let first = run(&middleware, &calls, get, None).serialise();
+        let get = "GET /a HTTP/1.1\r\nHost: two.example\r\n\r\n";
+        let second = run(&middleware, &calls, get, None).serialise();
+        assert_ne!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn skip_no_store_and_expired() {
+        let middleware: Vec<Middleware> =
+            vec![Box::new(Cache::new(Duration::from_secs(60)).middleware())];
+        let calls = AtomicUsize::new(0);
+        run(&middleware, &calls, "GET /a HTTP/1.1\r\n\r\n", NO_STORE);
+        run(&middleware, &calls, "GET /a HTTP/1.1\r\n\r\n", NO_STORE);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let middleware: Vec<Middleware> =
+            vec![Box::new(Cache::new(Duration::ZERO).middleware())];
+        run(&middleware, &calls, "GET /a HTTP/1.1\r\n\r\n", None);
+        run(&middleware, &calls, "GET /a HTTP/1.1\r\n\r\n", None);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn skip_private_requests_and_responses() {
+        let middleware: Vec<Middleware> =
+            vec![Box::new(Cache::new(Duration::from_secs(60)).middleware())];
+        let calls = AtomicUsize::new(0);
+        for raw in [
+            "GET /a HTTP/1.1\r\nAuthorization: Bearer abc\r\n\r\n",
+            "GET /b HTTP/1.1\r\nCookie: session=abc\r\n\r\n",
+        ] {
+            run(&middleware, &calls, raw, None);
+            run(&middleware, &calls, raw, None);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        for (path, header) in [
+            ("/c", ("Set-Cookie", "session=abc")),
+            ("/d", ("Vary", "Accept-Encoding")),
+            ("/e", ("Cache-Control", "max-age=60, private")),
+        ] {
+            let raw = format!("GET {path} HTTP/1.1\r\n\r\n");
+            run(&middleware, &calls, &raw, Some(header));
+            run(&middleware, &calls, &raw, Some(header));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 10);
+    }
+
+    fn fill(cache: &Cache, paths: &[&str]) -> usize {
+        for path in paths {
+            let raw = format!("GET {path} HTTP/1.1\r\n\r\n");
+            let request = Request::from_bytes(raw.as_bytes()).unwrap();
+            cache.handle(&request, Next::new(&[], &|_| Response::text(path)));
+        }
+        cache.entries.lock().unwrap().len()
+    }
+
+    #[test]
+    fn bound_and_sweep_entries() {
+        let cache = Cache::new(Duration::from_secs(60)).max_entries(2);
+        assert_eq!(fill(&cache, &["/a", "/b", "/c"]), 2);
+        let key = (Method::Get, None, "/a".to_string());
+        assert!(!cache.entries.lock().unwrap().contains_key(&key));
+
+        let cache = Cache::new(Duration::ZERO);
+        assert_eq!(fill(&cache, &["/a", "/b", "/c"]), 1);
+    }
+}
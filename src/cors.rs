@@ -0,0 +1,228 @@
+use crate::{
+    http::{Method, Request, Response, StatusCode},
+    middleware::Next,
+};
+use std::{sync::Arc, time::Duration};
+
+#[derive(Debug, Clone, Default)]
+pub struct CorsConfig {
+    origins: Vec<String>,
+    methods: Vec<Method>,
+    headers: Vec<String>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl CorsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        self.origins.push(origin.trim_end_matches('/').into());
+        self
+    }
+
+    pub fn allow_method(mut self, method: Method) -> Self {
+        self.methods.push(method);
+        self
+    }
+
+    pub fn allow_header(mut self, header: &str) -> Self {
+        self.headers.push(header.into());
+        self
+    }
+
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn is_preflight(request: &Request) -> bool {
+        request.method() == &Method::Options
+            && request.header("origin").is_some()
+            && request.header("access-control-request-method").is_some()
+    }
+
+    pub fn headers(&self, request: &Request) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::new();
+        if self.origins.iter().any(|allowed| allowed != "*") {
+            headers.push(("Vary", "Origin".to_string()));
+        }
+        let Some((origin, credentials)) = self.allowed_origin(request) else {
+            return headers;
+        };
+        headers.push(("Access-Control-Allow-Origin", origin.to_string()));
+        if credentials {
+            headers.push(("Access-Control-Allow-Credentials", "true".into()));
+        }
+        if !Self::is_preflight(request) {
+            return headers;
+        }
+
+        if !self.methods.is_empty() {
+            let methods = self
+                .methods
+                .iter()
+                .map(Method::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            headers.push(("Access-Control-Allow-Methods", methods));
+        }
+        if !self.headers.is_empty() {
+            headers.push((
+                "Access-Control-Allow-Headers",
+                self.headers.join(", "),
+            ));
+        }
+        if let Some(max_age) = self.max_age {
+            headers.push((
+                "Access-Control-Max-Age",
+                max_age.as_secs().to_string(),
+            ));
+        }
+        headers
+    }
+
+    pub fn middleware(
+        self,
+    ) -> impl Fn(&Request, Next) -> Response + Send + Sync + 'static {
+        let cors = Arc::new(self);
+        move |request, next| cors.handle(request, next)
+    }
+
+    fn handle(&self, request: &Request, next: Next) -> Response {
+        let headers = self.headers(request);
+        let allowed = self.allowed_origin(request).is_some();
+        let response = match Self::is_preflight(request) && allowed {
+            true => Response::new().set_status_code(StatusCode::NoContent),
+            false => next.run(request),
+        };
+        headers
+            .into_iter()
+            .fold(response, |response, (key, value)| match key {
+                "Vary" => response.append_header(key, value),
+                _ => response.add_header(key, value),
+            })
+    }
+
+    // A wildcard match answers with a literal `*`, which browsers refuse to
+    // combine with credentials, so only explicitly listed origins get them.
+    fn allowed_origin<'a>(
+        &self,
+        request: &'a Request,
+    ) -> Option<(&'a str, bool)> {
+        let origin = request.header("origin")?;
+        if self.origins.iter().any(|allowed| allowed == origin) {
+            return Some((origin, self.credentials));
+        }
+        self.origins
+            .iter()
+            .any(|allowed| allowed == "*")
+            .then_some(("*", false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::Middleware;
+
+    fn run(middleware: &[Middleware], raw: &str) -> Response {
+        let request = Request::from_bytes(raw.as_bytes()).unwrap();
+        Next::new(middleware, &|_| Response::text("ok")).run(&request)
+    }
+
+    #[test]
+    fn short_circuit_preflight() {
+        let cors = CorsConfig::new()
+            .allow_origin("https://app.example")
+            .allow_method(Method::Get)
+            .allow_method(Method::Post)
+            .allow_header("Content-Type")
+            .allow_credentials(true)
+            .max_age(Duration::from_secs(600));
+        let middleware: Vec<Middleware> = vec![Box::new(cors.middleware())];
+
+        let response = run(
+            &middleware,
+            "OPTIONS /api HTTP/1.1\r\nOrigin: https://app.example\r\nAccess-Control-Request-Method: POST\r\n\r\n",
+        );
+        assert_eq!(response.status_code(), StatusCode::NoContent);
+        assert_eq!(
+            response.header("Access-Control-Allow-Origin"),
+            Some("https://app.example")
+        );
+        assert_eq!(
+            response.header("Access-Control-Allow-Methods"),
+            Some("GET, POST")
+        );
+        assert_eq!(
+            response.header("Access-Control-Allow-Headers"),
+            Some("Content-Type")
+        );
+        assert_eq!(
+            response.header("Access-Control-Allow-Credentials"),
+            Some("true")
+        );
+        assert_eq!(response.header("Access-Control-Max-Age"), Some("600"));
+    }
+
+    #[test]
+    fn echo_allowed_origins_only() {
+        let cors = CorsConfig::new().allow_origin("https://app.example");
+        let middleware: Vec<Middleware> = vec![Box::new(cors.middleware())];
+
+        let response = run(
+            &middleware,
+            "GET /api HTTP/1.1\r\nOrigin: https://app.example\r\n\r\n",
+        );
+        assert!(response.serialise().ends_with("ok"));
+        assert_eq!(
+            response.header("Access-Control-Allow-Origin"),
+            Some("https://app.example")
+        );
+        assert_eq!(response.header("Vary"), Some("Origin"));
+        assert_eq!(response.header("Access-Control-Allow-Methods"), None);
+
+        let response = run(
+            &middleware,
+            "OPTIONS /api HTTP/1.1\r\nOrigin: https://evil.example\r\nAccess-Control-Request-Method: GET\r\n\r\n",
+        );
+        assert_eq!(response.status_code(), StatusCode::Ok);
+        assert_eq!(response.header("Access-Control-Allow-Origin"), None);
+        assert_eq!(response.header("Vary"), Some("Origin"));
+    }
+
+    #[test]
+    fn wildcard_origin_without_credentials() {
+        let cors = CorsConfig::new().allow_origin("*").allow_credentials(true);
+        let request = Request::from_bytes(
+            b"GET / HTTP/1.1\r\nOrigin: https://any.example\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            cors.headers(&request),
+            [("Access-Control-Allow-Origin", "*".into())]
+        );
+
+        let cors = cors.allow_origin("https://app.example");
+        let request = Request::from_bytes(
+            b"GET / HTTP/1.1\r\nOrigin: https://app.example\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            cors.headers(&request),
+            [
+                ("Vary", "Origin".into()),
+                ("Access-Control-Allow-Origin", "https://app.example".into()),
+                ("Access-Control-Allow-Credentials", "true".into()),
+            ]
+        );
+    }
+}
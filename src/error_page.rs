@@ -0,0 +1,71 @@
+use crate::http::{Request, Response};
+
+pub type ErrorPage = Box<dyn Fn(&Request, Response) -> Response + Send + Sync>;
+
+pub fn default_error_page(request: &Request, response: Response) -> Response {
+    let status_code = response.status_code();
+    match request.preferred(&["text/html", "application/json"]) {
+        Some("application/json") => response
+            .add_header("Content-Type", "application/json")
+            .set_body(format!(
+                "{{\"status\":{},\"error\":\"{}\"}}",
+                status_code.code(),
+                status_code.reason()
+            )),
+        _ => response
+            .add_header("Content-Type", "text/html; charset=utf-8")
+            .set_body(format!(
+                "<!DOCTYPE html>\n<html><head><title>{status_code}</title></head><body><h1>{status_code}</h1></body></html>\n"
+            )),
+    }
+}
+
+pub(crate) fn needs_error_page(response: &Response) -> bool {
+    response.status_code().code() >= 400 && response.body_len() == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+
+    fn render(raw: &str, response: Response) -> Response {
+        let request = Request::from_bytes(raw.as_bytes()).unwrap();
+        default_error_page(&request, response)
+    }
+
+    #[test]
+    fn negotiate_error_body() {
+        let not_found = Response::empty(StatusCode::NotFound);
+        assert!(needs_error_page(&not_found));
+
+        let response = render("GET / HTTP/1.1\r\n\r\n", not_found.clone());
+        assert_eq!(
+            response.header("Content-Type"),
+            Some("text/html; charset=utf-8")
+        );
+        assert!(response.serialise().contains("<h1>404 Not Found</h1>"));
+
+        let response = render(
+            "GET / HTTP/1.1\r\nAccept: application/json\r\n\r\n",
+            not_found,
+        );
+        assert_eq!(response.header("Content-Type"), Some("application/json"));
+        assert!(response
+            .serialise()
+            .ends_with("{\"status\":404,\"error\":\"Not Found\"}"));
+    }
+
+    #[test]
+    fn keep_handler_bodies() {
+        assert!(!needs_error_page(&Response::new()));
+        assert!(!needs_error_page(
+            &Response::new()
+                .set_status_code(StatusCode::NotFound)
+                .set_body("custom")
+        ));
+        assert!(needs_error_page(
+            &Response::new().set_status_code(StatusCode::InternalServerError)
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{
+    http::{Request, Response, StatusCode},
+    mime::mime_from_extension,
+};
+use std::path::{Component, Path, PathBuf};
+
+pub fn serve_dir(
+    root: impl Into<PathBuf>,
+) -> impl Fn(&Request) -> Response + Send + Sync + 'static {
+    let root = root.into();
+    move |request| serve_file(&root, request.param("path").unwrap_or(""))
+}
+
+fn serve_file(root: &Path, path: &str) -> Response {
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return status_response(StatusCode::Forbidden);
+    }
+
+    let path = root.join(relative);
+    if !path.is_file() {
+        return status_response(StatusCode::NotFound);
+    }
+    match std::fs::read(&path) {
+        Ok(bytes) => Response::new()
+            .add_header("Content-Type", content_type(&path))
+            .set_body_bytes(bytes),
+        Err(_) => status_response(StatusCode::NotFound),
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    mime_from_extension(
+        path.extension().and_then(|ext| ext.to_str()).unwrap_or(""),
+    )
+}
+
+fn status_response(status_code: StatusCode) -> Response {
+    Response::new()
+        .set_status_code(status_code)
+        .set_body(status_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serve_files_from_root() {
+        let root = std::env::temp_dir().join("wee-server-serve-dir");
+        std::fs::create_dir_all(root.join("css")).unwrap();
+        std::fs::write(root.join("css/app.css"), "body {}").unwrap();
+
+        let response = serve_file(&root, "css/app.css").serialise();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/css\r\n"));
+        assert!(response.contains("Content-Length: 7\r\n"));
+        assert!(response.ends_with("\r\n\r\nbody {}"));
+
+        let response = serve_file(&root, "css/missing.css").serialise();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        for path in ["../etc/passwd", "css/../../etc/passwd", "/etc/passwd"] {
+            let response = serve_file(&root, path).serialise();
+            assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        }
+    }
+}
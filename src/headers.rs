@@ -0,0 +1,132 @@
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Headers {
+    entries: Vec<(String, String)>,
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn insert(&mut self, name: impl ToString, value: impl ToString) {
+        let name = name.to_string();
+        let mut value = Some(value.to_string());
+        self.entries.retain_mut(|(k, v)| {
+            if !k.eq_ignore_ascii_case(&name) {
+                return true;
+            }
+            match value.take() {
+                Some(value) => {
+                    *k = name.clone();
+                    *v = value;
+                    true
+                }
+                None => false,
+            }
+        });
+        if let Some(value) = value {
+            self.entries.push((name, value));
+        }
+    }
+
+    pub fn append(&mut self, name: impl ToString, value: impl ToString) {
+        self.entries.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn get_mut(&mut self, name: &str) -> Option<&mut String> {
+        self.entries
+            .iter_mut()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+}
+
+impl<K: ToString, V: ToString> FromIterator<(K, V)> for Headers {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut headers = Self::new();
+        for (key, value) in iter {
+            headers.append(key, value);
+        }
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_insensitive_lookup() {
+        let mut headers = Headers::new();
+        headers.append("Content-Type", "text/plain");
+        headers.append("Vary", "Accept");
+        headers.append("vary", "Accept-Encoding");
+
+        assert_eq!(headers.get("content-type"), Some("text/plain"));
+        assert_eq!(headers.get("VARY"), Some("Accept"));
+        assert_eq!(headers.get_all("Vary"), ["Accept", "Accept-Encoding"]);
+        assert!(headers.contains("CONTENT-TYPE"));
+        assert!(!headers.contains("Content-Length"));
+        assert!(headers.get_all("Content-Length").is_empty());
+    }
+
+    #[test]
+    fn insert_replaces_in_place() {
+        let mut headers: Headers =
+            [("X-First", "1"), ("X-Second", "2"), ("X-Third", "3")]
+                .into_iter()
+                .collect();
+        headers.append("x-second", "again");
+        headers.insert("x-second", "two");
+        headers.insert("X-Fourth", "4");
+        assert_eq!(
+            headers.iter().collect::<Vec<_>>(),
+            [
+                ("X-First", "1"),
+                ("x-second", "two"),
+                ("X-Third", "3"),
+                ("X-Fourth", "4"),
+            ]
+        );
+
+        headers.remove("x-first");
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers.get("X-First"), None);
+    }
+}
@@ -1,48 +1,201 @@
+mod accept;
+mod access_log;
+mod auth;
+mod base64;
+mod body;
+mod cache;
+mod cookie;
+mod cors;
+mod date;
+mod error_page;
+mod files;
+mod headers;
 mod http;
-pub use http::{Method, Request, Response};
+mod middleware;
+mod mime;
+mod multipart;
+mod pool;
+mod range;
+mod rate_limit;
+mod router;
+mod security;
+mod sha1;
+mod shutdown;
+pub use auth::Auth;
+pub use cache::Cache;
+pub use cookie::{Cookie, SameSite};
+pub use cors::CorsConfig;
+pub use error_page::{default_error_page, ErrorPage};
+pub use files::serve_dir;
+pub use headers::Headers;
+pub use http::{
+    percent_decode, ContentType, Error, Limits, Method, Protocol, Request,
+    RequestRef, Response, StatusCode,
+};
+pub use middleware::{Middleware, Next};
+pub use mime::mime_from_extension;
+pub use multipart::Part;
+pub use range::ByteRange;
+pub use rate_limit::RateLimit;
+pub use router::{Endpoint, Handler, Params, Router, StreamHandler};
+pub use security::SecurityHeaders;
+pub use shutdown::ShutdownHandle;
 
-pub type Handler = fn(Request) -> Response;
+use body::{accepts_body, into_error, BodyReader};
+use pool::ThreadPool;
+use shutdown::Connections;
 
 use std::{
-    collections::HashMap,
-    io::{Read, Write},
-    net::{TcpListener, TcpStream, ToSocketAddrs},
+    cell::RefCell,
+    io::{self, Read},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    path::PathBuf,
     sync::Arc,
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-#[cfg(feature = "log")]
-use log::info;
-
 #[cfg(feature = "tls")]
 use rustls::{ServerConfig, ServerConnection};
 #[cfg(feature = "tls")]
-use std::{fs::File, io::BufReader, path::Path};
+use std::{
+    fs::File,
+    io::{BufReader, Write},
+    path::Path,
+};
 
 pub struct Server {
     listener: TcpListener,
     #[cfg(feature = "tls")]
     tls_config: Option<ServerConfig>,
-    paths: HashMap<String, Handler>,
+    router: Router,
+    limits: Limits,
+    // Each open connection holds a worker, including one idling between
+    // kept-alive requests for up to the keep-alive timeout
+    workers: usize,
+    access_log: bool,
+    shutdown: ShutdownHandle,
+    drain_timeout: Duration,
 }
 
 impl Server {
-    pub fn bind(addr: impl ToSocketAddrs) -> Self {
-        Self {
-            listener: TcpListener::bind(addr).unwrap(),
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let shutdown = ShutdownHandle::new(listener.local_addr()?);
+        let mut router = Router::new();
+        router.error_page(default_error_page);
+        Ok(Self {
+            listener,
             #[cfg(feature = "tls")]
             tls_config: None,
-            paths: HashMap::new(),
-        }
+            router,
+            limits: Limits::default(),
+            workers: thread::available_parallelism().map_or(4, |n| n.get() * 4),
+            access_log: false,
+            shutdown,
+            drain_timeout: Duration::from_secs(30),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn path(
+        mut self,
+        path: &str,
+        handler: impl Fn(&Request) -> Response + Send + Sync + 'static,
+    ) -> Self {
+        self.router.any(path, handler);
+        self
+    }
+
+    pub fn route(
+        mut self,
+        method: Method,
+        path: &str,
+        handler: impl Fn(&Request) -> Response + Send + Sync + 'static,
+    ) -> Self {
+        self.router.add(method, path, handler);
+        self
+    }
+
+    pub fn files(mut self, prefix: &str, root: impl Into<PathBuf>) -> Self {
+        let path = format!("{}/*path", prefix.trim_end_matches('/'));
+        self.router.add(Method::Get, &path, serve_dir(root));
+        self
+    }
+
+    pub fn wrap(
+        mut self,
+        middleware: impl Fn(&Request, Next) -> Response + Send + Sync + 'static,
+    ) -> Self {
+        self.router.wrap(middleware);
+        self
+    }
+
+    pub fn stream(
+        mut self,
+        method: Method,
+        path: &str,
+        handler: impl Fn(&Request, &mut dyn Read) -> Response
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.router.add_streaming(method, path, handler);
+        self
+    }
+
+    pub fn error_page(
+        mut self,
+        render: impl Fn(&Request, Response) -> Response + Send + Sync + 'static,
+    ) -> Self {
+        self.router.error_page(render);
+        self
+    }
+
+    // Keeps the current error page unless the new router brings its own
+    pub fn router(mut self, mut router: Router) -> Self {
+        router.inherit_error_page(&mut self.router);
+        self.router = router;
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn access_log(mut self, enabled: bool) -> Self {
+        self.access_log = enabled;
+        self
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.limits = self.limits.read_timeout(read_timeout);
+        self
     }
 
-    pub fn path(mut self, path: &str, handler: Handler) -> Self {
-        self.paths
-            .insert(path.trim_end_matches('/').into(), handler);
+    pub fn keep_alive_timeout(mut self, keep_alive_timeout: Duration) -> Self {
+        self.limits = self.limits.keep_alive_timeout(keep_alive_timeout);
         self
     }
 
+    pub fn drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
     #[cfg(feature = "tls")]
     pub fn tls(
         mut self,
@@ -70,15 +223,43 @@ impl Server {
         self
     }
 
+    pub fn run(
+        mut self,
+        handler: impl Fn(&Request) -> Response + Send + Sync + 'static,
+    ) {
+        self.router.any("/*path", handler);
+        self.listen()
+    }
+
     pub fn listen(self) {
-        let paths = Arc::new(self.paths);
+        let router = Arc::new(self.router);
+        let limits = self.limits;
+        let access_log = self.access_log;
+        let shutdown = self.shutdown;
+        let connections = Arc::new(Connections::default());
+        let pool = ThreadPool::new(self.workers);
+        let incoming = self
+            .listener
+            .incoming()
+            .take_while(|_| !shutdown.is_shutdown());
 
         #[cfg(not(feature = "tls"))]
-        for stream in self.listener.incoming() {
-            let paths_clone = paths.clone();
+        for stream in incoming {
+            let router_clone = router.clone();
+            let shutdown_clone = shutdown.clone();
             match stream {
                 Ok(stream) => {
-                    thread::spawn(move || handle(stream, paths_clone));
+                    let connection = connections.track(&stream);
+                    pool.execute(move || {
+                        handle(
+                            stream,
+                            router_clone,
+                            limits,
+                            access_log,
+                            &shutdown_clone,
+                        );
+                        drop(connection);
+                    });
                 }
                 Err(err) => println!("{err:?}"),
             };
@@ -88,12 +269,14 @@ impl Server {
         match self.tls_config {
             Some(tls_config) => {
                 let tls_config = Arc::new(tls_config);
-                for stream in self.listener.incoming() {
+                for stream in incoming {
                     match stream {
                         Ok(stream) => {
                             let tls_config_clone = tls_config.clone();
-                            thread::spawn(move || {
-                                handle_tls(stream, tls_config_clone)
+                            let connection = connections.track(&stream);
+                            pool.execute(move || {
+                                handle_tls(stream, tls_config_clone);
+                                drop(connection);
                             });
                         }
                         Err(err) => println!("{err:?}"),
@@ -101,17 +284,30 @@ impl Server {
                 }
             }
             None => {
-                for stream in self.listener.incoming() {
-                    let paths_clone = paths.clone();
+                for stream in incoming {
+                    let router_clone = router.clone();
+                    let shutdown_clone = shutdown.clone();
                     match stream {
                         Ok(stream) => {
-                            thread::spawn(move || handle(stream, paths_clone));
+                            let connection = connections.track(&stream);
+                            pool.execute(move || {
+                                handle(
+                                    stream,
+                                    router_clone,
+                                    limits,
+                                    access_log,
+                                    &shutdown_clone,
+                                );
+                                drop(connection);
+                            });
                         }
                         Err(err) => println!("{err:?}"),
                     };
                 }
             }
         }
+
+        connections.drain(self.drain_timeout);
     }
 }
 
@@ -120,26 +316,249 @@ fn set_stream_timeouts(stream: &TcpStream, duration: Duration) {
     stream.set_write_timeout(Some(duration)).unwrap();
 }
 
-fn handle(mut stream: TcpStream, paths: Arc<HashMap<String, Handler>>) {
+fn handle(
+    mut stream: TcpStream,
+    router: Arc<Router>,
+    limits: Limits,
+    access_log: bool,
+    shutdown: &ShutdownHandle,
+) {
     println!("{stream:?}");
-    set_stream_timeouts(&stream, Duration::from_millis(1000));
+    set_stream_timeouts(&stream, limits.read_timeout);
+
+    let mut buf = Vec::new();
+    let mut idle_timeout = limits.read_timeout;
+    loop {
+        let request = read_head(&mut stream, &mut buf, &limits, idle_timeout);
+        idle_timeout = limits.keep_alive_timeout;
+        let start = Instant::now();
+        let entry = match (&request, access_log) {
+            (Some(request), true) => Some(access_log::Entry::new(
+                stream.peer_addr().ok(),
+                request.as_ref().ok(),
+            )),
+            _ => None,
+        };
+        let (response, keep_alive) = match request {
+            Some(Ok(request)) => {
+                match respond(
+                    request,
+                    &router,
+                    &mut stream,
+                    &mut buf,
+                    &limits,
+                    shutdown,
+                ) {
+                    Some(response) => response,
+                    None => return,
+                }
+            }
+            Some(Err(err)) => close_with_error(&err),
+            None => return,
+        };
+
+        let written = response.write_to(&mut stream);
+        if let Some(entry) = entry {
+            entry.log(&response, start.elapsed());
+        }
+        if written.is_err() || !keep_alive {
+            return;
+        }
+    }
+}
+
+fn respond(
+    mut request: Request,
+    router: &Router,
+    stream: &mut TcpStream,
+    buf: &mut Vec<u8>,
+    limits: &Limits,
+    shutdown: &ShutdownHandle,
+) -> Option<(Response, bool)> {
+    if request.protocol() == &Protocol::Http2 {
+        return Some((
+            version_not_supported().add_header("Connection", "close"),
+            false,
+        ));
+    }
 
-    let mut recv_buf = [0u8; 2048];
-    let len = stream.read(&mut recv_buf).unwrap();
-    let request = Request::from_bytes(&recv_buf[..len]);
     println!("{request:?}");
+    request.set_peer_addr(stream.peer_addr().ok());
+    let protocol = *request.protocol();
+    let is_head = request.method() == &Method::Head;
+    let mut keep_alive = request.is_keep_alive() && !shutdown.is_shutdown();
+
+    if request.expects_continue() {
+        if let Err(err) = accepts_body(&request, limits) {
+            return Some(close_with_error(&err));
+        }
+        Response::continue_100().write_to(stream).ok()?;
+    }
+    let mut stream = Deadline {
+        stream,
+        deadline: Instant::now() + limits.read_timeout,
+    };
+    let mut body = match BodyReader::new(&request, buf, &mut stream, limits) {
+        Ok(body) => body,
+        Err(err) => return Some(close_with_error(&err)),
+    };
+
+    let route = router.route(&request);
+    if !matches!(route, Ok((Endpoint::Streaming(_), _))) {
+        let mut bytes = Vec::new();
+        if let Err(err) = body.read_to_end(&mut bytes) {
+            if is_timeout(&err) {
+                return Some(close_with_error(&Error::RequestTimeout));
+            }
+            return Some(close_with_error(&into_error(err)?));
+        }
+        request.add_trailers(body.take_trailers());
+        if let Err(err) = request.set_encoded_body(bytes, limits.max_body_bytes)
+        {
+            return Some(close_with_error(&err));
+        }
+    }
+
+    let mut response = match route {
+        Ok((Endpoint::Streaming(handler), params)) => {
+            request.set_params(params);
+            let body = RefCell::new(&mut body);
+            let response = router.run(&request, &|request| {
+                handler(request, &mut **body.borrow_mut())
+            });
+            keep_alive &= io::copy(*body.borrow_mut(), &mut io::sink()).is_ok();
+            response
+        }
+        Ok((Endpoint::Buffered(handler), params)) => {
+            request.set_params(params);
+            router.run(&request, handler)
+        }
+        Err(StatusCode::NotFound) => router.run(&request, &|_| not_found()),
+        Err(StatusCode::MethodNotAllowed) => {
+            router.run(&request, &|request| router.allow_response(request))
+        }
+        Err(status_code) => router.run(&request, &|_| {
+            Response::new()
+                .set_status_code(status_code)
+                .set_body(status_code)
+        }),
+    }
+    .set_protocol(protocol);
+    if is_head {
+        response = response.head_only();
+    }
+    keep_alive &= is_head || response.is_delimited();
+    match (protocol, keep_alive) {
+        (Protocol::Http1_1, false) => {
+            response = response.add_header("Connection", "close");
+        }
+        (Protocol::Http1_0, true) => {
+            response = response.add_header("Connection", "keep-alive");
+        }
+        _ => {}
+    }
+    Some((response, keep_alive))
+}
+
+fn read_head(
+    stream: &mut TcpStream,
+    buf: &mut Vec<u8>,
+    limits: &Limits,
+    idle_timeout: Duration,
+) -> Option<Result<Request, Error>> {
+    let mut recv_buf = [0u8; 2048];
+    // Waiting for the next request is bounded separately from reading it
+    let mut deadline = Instant::now()
+        + match buf.is_empty() {
+            true => idle_timeout,
+            false => limits.read_timeout,
+        };
+    loop {
+        match Request::parse_head_prefix(buf, limits) {
+            Ok(Some((request, used))) => {
+                buf.drain(..used);
+                return Some(Ok(request));
+            }
+            Ok(None) => {}
+            Err(err) => return Some(Err(err)),
+        }
+
+        let timed_out = || match buf.is_empty() {
+            true => None,
+            false => Some(Err(Error::RequestTimeout)),
+        };
+        let Some(remaining) = deadline
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+        else {
+            return timed_out();
+        };
+        stream.set_read_timeout(Some(remaining)).ok()?;
+        let len = match stream.read(&mut recv_buf) {
+            Ok(0) => return None,
+            Ok(len) => len,
+            Err(err) if is_timeout(&err) => return timed_out(),
+            Err(_) => return None,
+        };
+        if buf.is_empty() {
+            deadline = Instant::now() + limits.read_timeout;
+        }
+        buf.extend_from_slice(&recv_buf[..len]);
+    }
+}
 
-    let mut response: Response = match paths.get(request.path()) {
-        Some(handler) => handler(request),
-        None => not_found(),
+// Bounds the whole body read rather than each individual read, so a client
+// trickling bytes cannot hold a worker past the read timeout.
+struct Deadline<'a> {
+    stream: &'a mut TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self
+            .deadline
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or(io::ErrorKind::TimedOut)?;
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+fn close_with_error(err: &Error) -> (Response, bool) {
+    println!("{err:?}");
+    (error_response(err).add_header("Connection", "close"), false)
+}
+
+fn error_response(err: &Error) -> Response {
+    let status_code = match err {
+        Error::HeadersTooLarge => StatusCode::RequestHeaderFieldsTooLarge,
+        Error::BodyTooLarge => StatusCode::PayloadTooLarge,
+        Error::RequestTimeout => StatusCode::RequestTimeout,
+        _ => StatusCode::BadRequest,
     };
+    Response::new()
+        .set_status_code(status_code)
+        .set_body(status_code)
+}
 
-    stream.write(response.serialise().as_bytes()).unwrap();
+fn version_not_supported() -> Response {
+    Response::new()
+        .set_status_code(StatusCode::HttpVersionNotSupported)
+        .set_body(StatusCode::HttpVersionNotSupported)
 }
 
 fn not_found() -> Response {
     Response::new()
-        .set_status_code(http::StatusCode::NotFound)
+        .set_status_code(StatusCode::NotFound)
         .set_body("404 Not Found\nOops! Looks like Nessie took our page for a swim in the Loch")
 }
 
@@ -162,3 +581,284 @@ fn handle_tls(mut stream: TcpStream, tls_config: Arc<ServerConfig>) {
     conn.write_tls(&mut stream).unwrap();
     conn.process_new_packets().unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn spawn(server: Server) -> SocketAddr {
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.listen());
+        addr
+    }
+
+    fn read_until_closed(stream: &mut TcpStream) -> String {
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response);
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    #[test]
+    fn body_read_deadline() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |_| Response::text("ok"))
+            .read_timeout(Duration::from_millis(200));
+        let mut stream = TcpStream::connect(spawn(server)).unwrap();
+        stream
+            .write_all(b"POST / HTTP/1.1\r\nContent-Length: 100\r\n\r\n")
+            .unwrap();
+
+        let start = Instant::now();
+        for _ in 0..60 {
+            thread::sleep(Duration::from_millis(50));
+            if stream.write_all(b"a").is_err() {
+                break;
+            }
+        }
+        let response = read_until_closed(&mut stream);
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn serve_connections_concurrently() {
+        const WORKERS: usize = 4;
+        let barrier = Arc::new(std::sync::Barrier::new(WORKERS));
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .workers(WORKERS)
+            .path("/", move |_| {
+                barrier.wait();
+                Response::text("ok")
+            });
+        let addr = spawn(server);
+
+        let mut streams: Vec<TcpStream> = (0..WORKERS)
+            .map(|_| {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                stream
+                    .set_read_timeout(Some(Duration::from_secs(5)))
+                    .unwrap();
+                stream
+                    .write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+                stream
+            })
+            .collect();
+        for stream in &mut streams {
+            let response = read_until_closed(stream);
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with("ok"));
+        }
+    }
+
+    #[test]
+    fn close_after_read_timeout() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |_| Response::text("ok"))
+            .read_timeout(Duration::from_millis(200));
+        let addr = spawn(server);
+
+        let mut idle = TcpStream::connect(addr).unwrap();
+        let mut partial = TcpStream::connect(addr).unwrap();
+        partial.write_all(b"GET / HTTP/1.1\r\nHost: ").unwrap();
+        let start = Instant::now();
+        assert_eq!(read_until_closed(&mut idle), "");
+        read_until_closed(&mut partial);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(150), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    }
+
+    #[test]
+    fn partial_head_times_out() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |_| Response::text("ok"))
+            .read_timeout(Duration::from_millis(100));
+        let mut stream = TcpStream::connect(spawn(server)).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n").unwrap();
+
+        let response = read_until_closed(&mut stream);
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+        assert!(response.contains("Connection: close\r\n"));
+    }
+
+    #[test]
+    fn keep_alive_until_close() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/*path", |request| Response::text(request.path()));
+        let addr = spawn(server);
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"GET /a HTTP/1.1\r\n\r\nGET /b HTTP/1.1\r\n\r\n")
+            .unwrap();
+        let mut response = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !response.ends_with(b"/b") {
+            let len = stream.read(&mut buf).unwrap();
+            assert_ne!(len, 0);
+            response.extend_from_slice(&buf[..len]);
+        }
+        let response = String::from_utf8(response).unwrap();
+        assert_eq!(response.matches("HTTP/1.1 200 OK\r\n").count(), 2);
+        assert!(response.contains("\r\n\r\n/a"));
+
+        stream
+            .write_all(b"GET /c HTTP/1.1\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.contains("Connection: close\r\n"));
+        assert!(response.ends_with("/c"));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream.write_all(b"GET /d HTTP/1.0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(response.ends_with("/d"));
+    }
+
+    #[test]
+    fn stamp_peer_addr() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |request| Response::text(request.peer_addr().unwrap()));
+        let mut stream = TcpStream::connect(spawn(server)).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let peer_addr = stream.local_addr().unwrap().to_string();
+        assert!(response.ends_with(&peer_addr), "{response}");
+    }
+
+    #[test]
+    fn continue_only_for_accepted_bodies() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |request| Response::text(request.body_bytes().len()))
+            .limits(Limits::new().max_body_bytes(4));
+        let addr = spawn(server);
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"POST / HTTP/1.1\r\nExpect: 100-continue\r\nContent-Length: 5\r\n\r\n")
+            .unwrap();
+        let response = read_until_closed(&mut stream);
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"POST / HTTP/1.1\r\nExpect: 100-continue\r\nContent-Length: 4\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut buf = [0u8; 25];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"HTTP/1.1 100 Continue\r\n\r\n");
+        stream.write_all(b"body").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("4"));
+    }
+
+    #[test]
+    fn swapped_router_keeps_error_page() {
+        let request = Request::from_bytes(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let not_found = |_: &Request| Response::empty(StatusCode::NotFound);
+
+        let server = Server::bind("127.0.0.1:0").unwrap().router(Router::new());
+        let response = server.router.run(&request, &not_found);
+        assert!(response.serialise().contains("<h1>404 Not Found</h1>"));
+
+        let mut router = Router::new();
+        router.error_page(|_, response| response.set_body("custom"));
+        let server = Server::bind("127.0.0.1:0").unwrap().router(router);
+        let response = server.router.run(&request, &not_found);
+        assert!(response.serialise().ends_with("custom"));
+    }
+
+    #[test]
+    fn keep_alive_empty_body() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |_| Response::new());
+        let mut stream = TcpStream::connect(spawn(server)).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+
+        let mut buf = [0u8; 1024];
+        for _ in 0..2 {
+            stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+            let mut response = Vec::new();
+            while !response.ends_with(b"\r\n\r\n") {
+                let len = stream.read(&mut buf).unwrap();
+                assert_ne!(len, 0);
+                response.extend_from_slice(&buf[..len]);
+            }
+            let response = String::from_utf8(response).unwrap();
+            assert!(response.contains("Connection: keep-alive\r\n"));
+            assert!(response.contains("Content-Length: 0\r\n"));
+        }
+    }
+
+    #[test]
+    fn reject_http2_preface() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |_| Response::text("ok"));
+        let mut stream = TcpStream::connect(spawn(server)).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+            .unwrap();
+        let response = read_until_closed(&mut stream);
+        assert!(
+            response.starts_with("HTTP/1.1 505 HTTP Version Not Supported\r\n")
+        );
+        assert!(response.contains("Connection: close\r\n"));
+    }
+
+    #[test]
+    fn close_idle_keep_alive() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |_| Response::text("ok"))
+            .keep_alive_timeout(Duration::from_millis(100));
+        let mut stream = TcpStream::connect(spawn(server)).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+
+        let start = Instant::now();
+        let response = read_until_closed(&mut stream);
+        let elapsed = start.elapsed();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(!response.contains("Connection: close"));
+        assert!(elapsed >= Duration::from_millis(100), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    }
+}
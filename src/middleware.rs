@@ -0,0 +1,66 @@
+use crate::http::{Request, Response};
+
+pub type Middleware = Box<dyn Fn(&Request, Next) -> Response + Send + Sync>;
+
+pub struct Next<'a> {
+    middleware: &'a [Middleware],
+    handler: &'a dyn Fn(&Request) -> Response,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        middleware: &'a [Middleware],
+        handler: &'a dyn Fn(&Request) -> Response,
+    ) -> Self {
+        Self {
+            middleware,
+            handler,
+        }
+    }
+
+    pub fn run(self, request: &Request) -> Response {
+        match self.middleware.split_first() {
+            Some((first, rest)) => {
+                first(request, Next::new(rest, self.handler))
+            }
+            None => (self.handler)(request),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_middleware_in_order() {
+        let middleware: Vec<Middleware> = vec![
+            Box::new(|request, next| next.run(request).add_header("X-A", "1")),
+            Box::new(|request, next| {
+                next.run(request)
+                    .add_header("X-A", "2")
+                    .add_header("X-B", "2")
+            }),
+        ];
+        let request = Request::from_bytes(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let response = Next::new(&middleware, &|_| Response::new())
+            .run(&request)
+            .without_date()
+            .serialise();
+        assert_eq!(
+            response,
+            "HTTP/1.1 200 OK\r\nX-A: 1\r\nX-B: 2\r\nConnection: keep-alive\r\nContent-Length: 0\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn middleware_can_short_circuit() {
+        let middleware: Vec<Middleware> =
+            vec![Box::new(|_, _| Response::new().set_body("blocked"))];
+        let request = Request::from_bytes(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let response = Next::new(&middleware, &|_| unreachable!())
+            .run(&request)
+            .serialise();
+        assert!(response.ends_with("blocked"));
+    }
+}
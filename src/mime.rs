@@ -0,0 +1,32 @@
+pub fn mime_from_extension(ext: &str) -> &'static str {
+    match ext.trim_start_matches('.').to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "txt" => "text/plain",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mime_types() {
+        assert_eq!(mime_from_extension("html"), "text/html");
+        assert_eq!(mime_from_extension("CSS"), "text/css");
+        assert_eq!(mime_from_extension(".js"), "text/javascript");
+        assert_eq!(mime_from_extension("wasm"), "application/wasm");
+        assert_eq!(mime_from_extension("ico"), "image/x-icon");
+        assert_eq!(mime_from_extension("tar"), "application/octet-stream");
+        assert_eq!(mime_from_extension(""), "application/octet-stream");
+    }
+}
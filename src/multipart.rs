@@ -0,0 +1,93 @@
+use crate::{headers::Headers, http::Error};
+
+#[derive(Debug)]
+pub struct Part {
+    name: String,
+    filename: Option<String>,
+    headers: Headers,
+    bytes: Vec<u8>,
+}
+
+impl Part {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)
+    }
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+    }
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    pub fn text(&self) -> Result<&str, Error> {
+        std::str::from_utf8(&self.bytes).map_err(|_| Error::InvalidUtf8)
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<Self, Error> {
+        let header_end =
+            find(buf, b"\r\n\r\n").ok_or(Error::InvalidMultipart)?;
+        let raw_headers = std::str::from_utf8(&buf[..header_end])
+            .map_err(|_| Error::InvalidMultipart)?;
+
+        let mut headers = Headers::new();
+        for header in raw_headers.lines() {
+            let (key, value) =
+                header.split_once(':').ok_or(Error::InvalidMultipart)?;
+            headers.append(key.trim(), value.trim());
+        }
+
+        let mut name = None;
+        let mut filename = None;
+        let disposition = headers.get("content-disposition").unwrap_or("");
+        for param in disposition.split(';').skip(1) {
+            let Some((key, value)) = param.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "name" => name = Some(value),
+                "filename" => filename = Some(value),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or(Error::InvalidMultipart)?,
+            filename,
+            headers,
+            bytes: buf[header_end + 4..].to_vec(),
+        })
+    }
+}
+
+pub(crate) fn parse(boundary: &str, body: &[u8]) -> Result<Vec<Part>, Error> {
+    let delimiter = [b"--", boundary.as_bytes()].concat();
+    let start = find(body, &delimiter).ok_or(Error::InvalidMultipart)?;
+    let mut rest = &body[start + delimiter.len()..];
+
+    let delimiter = [b"\r\n", delimiter.as_slice()].concat();
+    let mut parts = Vec::new();
+    loop {
+        if rest.starts_with(b"--") {
+            return Ok(parts);
+        }
+        rest = rest.strip_prefix(b"\r\n").ok_or(Error::InvalidMultipart)?;
+        let end = find(rest, &delimiter).ok_or(Error::InvalidMultipart)?;
+        parts.push(Part::from_bytes(&rest[..end])?);
+        rest = &rest[end + delimiter.len()..];
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
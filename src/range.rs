@@ -0,0 +1,37 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    FromTo(u64, u64),
+    From(u64),
+    Suffix(u64),
+}
+
+impl ByteRange {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let (unit, range) = value.trim().split_once('=')?;
+        if !unit.trim().eq_ignore_ascii_case("bytes") || range.contains(',') {
+            return None;
+        }
+        let (start, end) = range.trim().split_once('-')?;
+        match (start.trim(), end.trim()) {
+            ("", "") => None,
+            ("", len) => Some(Self::Suffix(len.parse().ok()?)),
+            (start, "") => Some(Self::From(start.parse().ok()?)),
+            (start, end) => {
+                let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+                (start <= end).then_some(Self::FromTo(start, end))
+            }
+        }
+    }
+
+    pub fn resolve(&self, len: u64) -> Option<(u64, u64)> {
+        let (start, end) = match *self {
+            Self::FromTo(start, end) => (start, end.min(len.checked_sub(1)?)),
+            Self::From(start) => (start, len.checked_sub(1)?),
+            Self::Suffix(0) => return None,
+            Self::Suffix(suffix) => {
+                (len.saturating_sub(suffix), len.checked_sub(1)?)
+            }
+        };
+        (start <= end).then_some((start, end))
+    }
+}
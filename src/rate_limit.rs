@@ -0,0 +1,188 @@
+use crate::{
+    http::{Request, Response, StatusCode},
+    middleware::Next,
+};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+pub struct RateLimit {
+    rate: f64,
+    burst: f64,
+    max_buckets: usize,
+    trusted_proxies: Vec<IpAddr>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimit {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        Self {
+            rate: requests_per_second,
+            burst: burst.max(1) as f64,
+            max_buckets: 1024,
+            trusted_proxies: Vec::new(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn max_buckets(mut self, max_buckets: usize) -> Self {
+        self.max_buckets = max_buckets.max(1);
+        self
+    }
+
+    pub fn trust_proxy(mut self, proxy: IpAddr) -> Self {
+        self.trusted_proxies.push(proxy);
+        self
+    }
+
+    pub fn middleware(
+        self,
+    ) -> impl Fn(&Request, Next) -> Response + Send + Sync + 'static {
+        let limit = Arc::new(self);
+        move |request, next| limit.handle(request, next)
+    }
+
+    fn handle(&self, request: &Request, next: Next) -> Response {
+        match self.take(&self.client_key(request), Instant::now()) {
+            Ok(()) => next.run(request),
+            Err(retry_after) => Response::new()
+                .set_status_code(StatusCode::TooManyRequests)
+                .add_header("Retry-After", retry_after.as_secs().max(1))
+                .set_body(StatusCode::TooManyRequests),
+        }
+    }
+
+    fn take(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return Ok(());
+        };
+        if !buckets.contains_key(key) && buckets.len() >= self.max_buckets {
+            buckets.retain(|_, bucket| !self.refill(bucket, now));
+            // Every bucket is in use, so drop the one closest to full
+            if buckets.len() >= self.max_buckets {
+                let fullest = buckets
+                    .iter()
+                    .max_by(|(_, a), (_, b)| a.tokens.total_cmp(&b.tokens))
+                    .map(|(key, _)| key.clone());
+                if let Some(fullest) = fullest {
+                    buckets.remove(&fullest);
+                }
+            }
+        }
+
+        let bucket = buckets.entry(key.into()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        self.refill(bucket, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let wait = (1.0 - bucket.tokens) / self.rate;
+        Err(Duration::try_from_secs_f64(wait.ceil()).unwrap_or(Duration::MAX))
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(bucket.updated);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.rate).min(self.burst);
+        bucket.updated = now;
+        bucket.tokens >= self.burst
+    }
+
+    // X-Forwarded-For is client controlled, so it is only followed back
+    // through proxies that were explicitly trusted
+    fn client_key(&self, request: &Request) -> String {
+        let Some(mut client) = request.peer_addr().map(|addr| addr.ip()) else {
+            return String::new();
+        };
+        for hop in request.forwarded_for().into_iter().rev() {
+            if !self.trusted_proxies.contains(&client) {
+                break;
+            }
+            match hop.parse() {
+                Ok(ip) => client = ip,
+                Err(_) => return hop.to_string(),
+            }
+        }
+        client.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::Middleware;
+
+    fn run(middleware: &[Middleware], peer: &str, forwarded: &str) -> Response {
+        let raw =
+            format!("GET / HTTP/1.1\r\nX-Forwarded-For: {forwarded}\r\n\r\n");
+        let mut request = Request::from_bytes(raw.as_bytes()).unwrap();
+        request.set_peer_addr(Some(format!("{peer}:4321").parse().unwrap()));
+        Next::new(middleware, &|_| Response::text("ok")).run(&request)
+    }
+
+    #[test]
+    fn limit_per_client() {
+        let middleware: Vec<Middleware> =
+            vec![Box::new(RateLimit::new(0.5, 2).middleware())];
+
+        let ok = |peer, forwarded| {
+            run(&middleware, peer, forwarded).status_code() == StatusCode::Ok
+        };
+        assert!(ok("10.0.0.1", "192.0.2.1"));
+        assert!(ok("10.0.0.1", "192.0.2.2"));
+        let response = run(&middleware, "10.0.0.1", "192.0.2.3");
+        assert_eq!(response.status_code(), StatusCode::TooManyRequests);
+        assert_eq!(response.header("Retry-After"), Some("2"));
+
+        assert!(ok("10.0.0.2", "192.0.2.1"));
+    }
+
+    #[test]
+    fn follow_trusted_proxies() {
+        let limit = RateLimit::new(0.5, 1)
+            .trust_proxy("10.0.0.1".parse().unwrap())
+            .trust_proxy("10.0.0.2".parse().unwrap());
+        let middleware: Vec<Middleware> = vec![Box::new(limit.middleware())];
+
+        let ok = |peer, forwarded| {
+            run(&middleware, peer, forwarded).status_code() == StatusCode::Ok
+        };
+        assert!(ok("10.0.0.1", "192.0.2.1, 10.0.0.2"));
+        assert!(!ok("10.0.0.1", "192.0.2.1"));
+        assert!(ok("10.0.0.1", "192.0.2.2"));
+        assert!(ok("10.0.0.1", "198.51.100.1, 192.0.2.3"));
+        assert!(!ok("10.0.0.1", "198.51.100.2, 192.0.2.3"));
+    }
+
+    #[test]
+    fn refill_over_time() {
+        let limit = RateLimit::new(2.0, 1);
+        let now = Instant::now();
+        assert!(limit.take("a", now).is_ok());
+        assert_eq!(limit.take("a", now), Err(Duration::from_secs(1)));
+        assert!(limit.take("a", now + Duration::from_millis(500)).is_ok());
+    }
+
+    #[test]
+    fn bound_buckets() {
+        let limit = RateLimit::new(0.5, 2).max_buckets(2);
+        let now = Instant::now();
+        for key in ["a", "b", "b", "c"] {
+            assert!(limit.take(key, now).is_ok());
+        }
+        let buckets = limit.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert!(buckets.contains_key("b"));
+    }
+}
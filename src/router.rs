@@ -0,0 +1,416 @@
+use crate::{
+    error_page::{needs_error_page, ErrorPage},
+    http::{normalise_path, Method, Request, Response, StatusCode},
+    middleware::{Middleware, Next},
+};
+use std::{collections::HashMap, io::Read};
+
+pub type Handler = Box<dyn Fn(&Request) -> Response + Send + Sync>;
+pub type StreamHandler =
+    Box<dyn Fn(&Request, &mut dyn Read) -> Response + Send + Sync>;
+pub type Params = HashMap<String, String>;
+
+enum Segment {
+    Static(String),
+    Param(String),
+    CatchAll(String),
+}
+
+pub enum Endpoint {
+    Buffered(Handler),
+    Streaming(StreamHandler),
+}
+
+struct Route {
+    method: Option<Method>,
+    segments: Vec<Segment>,
+    endpoint: Endpoint,
+}
+
+impl Route {
+    fn matches(&self, path: &str) -> Option<Params> {
+        let mut parts = path.split('/');
+        let mut params = Params::new();
+        for segment in &self.segments {
+            if let Segment::CatchAll(name) = segment {
+                let rest = parts.collect::<Vec<_>>().join("/");
+                params.insert(name.clone(), rest);
+                return Some(params);
+            }
+            let part = parts.next()?;
+            match segment {
+                Segment::Static(name) if name == part => {}
+                Segment::Param(name) if !part.is_empty() => {
+                    params.insert(name.clone(), part.into());
+                }
+                _ => return None,
+            }
+        }
+        parts.next().is_none().then_some(params)
+    }
+
+    fn specificity(&self) -> Vec<u8> {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Static(_) => 2,
+                Segment::Param(_) => 1,
+                Segment::CatchAll(_) => 0,
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct Router {
+    routes: Vec<Route>,
+    middleware: Vec<Middleware>,
+    error_page: Option<ErrorPage>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(
+        &mut self,
+        method: Method,
+        path: &str,
+        handler: impl Fn(&Request) -> Response + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.insert(Some(method), path, Endpoint::Buffered(Box::new(handler)))
+    }
+
+    pub fn add_streaming(
+        &mut self,
+        method: Method,
+        path: &str,
+        handler: impl Fn(&Request, &mut dyn Read) -> Response
+            + Send
+            + Sync
+            + 'static,
+    ) -> &mut Self {
+        self.insert(Some(method), path, Endpoint::Streaming(Box::new(handler)))
+    }
+
+    pub fn any(
+        &mut self,
+        path: &str,
+        handler: impl Fn(&Request) -> Response + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.insert(None, path, Endpoint::Buffered(Box::new(handler)))
+    }
+
+    pub fn wrap(
+        &mut self,
+        middleware: impl Fn(&Request, Next) -> Response + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    pub fn error_page(
+        &mut self,
+        render: impl Fn(&Request, Response) -> Response + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.error_page = Some(Box::new(render));
+        self
+    }
+
+    pub(crate) fn inherit_error_page(&mut self, from: &mut Router) {
+        if self.error_page.is_none() {
+            self.error_page = from.error_page.take();
+        }
+    }
+
+    pub(crate) fn run(
+        &self,
+        request: &Request,
+        handler: &dyn Fn(&Request) -> Response,
+    ) -> Response {
+        let response = Next::new(&self.middleware, handler).run(request);
+        match &self.error_page {
+            Some(render) if needs_error_page(&response) => {
+                render(request, response)
+            }
+            _ => response,
+        }
+    }
+
+    fn insert(
+        &mut self,
+        method: Option<Method>,
+        path: &str,
+        endpoint: Endpoint,
+    ) -> &mut Self {
+        let segments: Vec<Segment> = normalise_path(path)
+            .split('/')
+            .map(|segment| {
+                if let Some(name) = segment.strip_prefix(':') {
+                    Segment::Param(name.into())
+                } else if let Some(name) = segment.strip_prefix('*') {
+                    Segment::CatchAll(name.into())
+                } else {
+                    Segment::Static(segment.into())
+                }
+            })
+            .collect();
+        assert!(
+            segments
+                .iter()
+                .rev()
+                .skip(1)
+                .all(|segment| !matches!(segment, Segment::CatchAll(_))),
+            "catch-all segment must be last in {path}"
+        );
+        self.routes.push(Route {
+            method,
+            segments,
+            endpoint,
+        });
+        self
+    }
+
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        let mut methods = Vec::new();
+        for route in &self.routes {
+            if let Some(method) = &route.method {
+                if !methods.contains(method) && route.matches(path).is_some() {
+                    methods.push(method.clone());
+                }
+            }
+        }
+        if methods.contains(&Method::Get) && !methods.contains(&Method::Head) {
+            methods.push(Method::Head);
+        }
+        if !methods.is_empty() && !methods.contains(&Method::Options) {
+            methods.push(Method::Options);
+        }
+        methods
+    }
+
+    pub(crate) fn allow_response(&self, request: &Request) -> Response {
+        let allow = self
+            .allowed_methods(request.path())
+            .iter()
+            .map(Method::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let response = match request.method() {
+            Method::Options => {
+                Response::new().set_status_code(StatusCode::NoContent)
+            }
+            _ => Response::new()
+                .set_status_code(StatusCode::MethodNotAllowed)
+                .set_body(StatusCode::MethodNotAllowed),
+        };
+        response.add_header("Allow", allow)
+    }
+
+    pub fn route(
+        &self,
+        request: &Request,
+    ) -> Result<(&Endpoint, Params), StatusCode> {
+        let mut best = self.find(request.path(), request.method());
+        if best.is_none() && request.method() == &Method::Head {
+            best = self.find(request.path(), &Method::Get);
+        }
+        let path_matched = || {
+            self.routes
+                .iter()
+                .any(|route| route.matches(request.path()).is_some())
+        };
+        match best {
+            Some((route, params)) => Ok((&route.endpoint, params)),
+            None if path_matched() => Err(StatusCode::MethodNotAllowed),
+            None => Err(StatusCode::NotFound),
+        }
+    }
+
+    fn find(&self, path: &str, method: &Method) -> Option<(&Route, Params)> {
+        let mut best: Option<(&Route, Params)> = None;
+        for route in &self.routes {
+            if !route.method.as_ref().is_none_or(|m| m == method) {
+                continue;
+            }
+            let Some(params) = route.matches(path) else {
+                continue;
+            };
+            if best
+                .as_ref()
+                .is_none_or(|(b, _)| route.specificity() > b.specificity())
+            {
+                best = Some((route, params));
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Request {
+        Request::from_bytes(raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn route_by_method_and_path() {
+        let mut router = Router::new();
+        router
+            .add(Method::Get, "/health", |_| Response::new().set_body("ok"))
+            .add(Method::Post, "/health", |_| {
+                Response::new().set_status_code(StatusCode::Created)
+            });
+
+        assert!(router.route(&parse("GET /health HTTP/1.1\r\n\r\n")).is_ok());
+        assert_eq!(
+            router
+                .route(&parse("DELETE /health HTTP/1.1\r\n\r\n"))
+                .err(),
+            Some(StatusCode::MethodNotAllowed)
+        );
+        assert_eq!(
+            router.route(&parse("GET /missing HTTP/1.1\r\n\r\n")).err(),
+            Some(StatusCode::NotFound)
+        );
+    }
+
+    #[test]
+    fn route_with_params() {
+        let mut router = Router::new();
+        router
+            .add(Method::Get, "/users/:id/posts/:post_id", |_| {
+                Response::new()
+            })
+            .add(Method::Get, "/users/me/posts/:post_id", |_| Response::new());
+
+        let (_, params) = router
+            .route(&parse("GET /users/42/posts/7 HTTP/1.1\r\n\r\n"))
+            .unwrap();
+        assert_eq!(params.get("id").map(String::as_str), Some("42"));
+        assert_eq!(params.get("post_id").map(String::as_str), Some("7"));
+
+        let (_, params) = router
+            .route(&parse("GET /users/me/posts/7 HTTP/1.1\r\n\r\n"))
+            .unwrap();
+        assert_eq!(params.get("id"), None);
+        assert_eq!(params.get("post_id").map(String::as_str), Some("7"));
+
+        assert_eq!(
+            router
+                .route(&parse("GET /users/42/posts HTTP/1.1\r\n\r\n"))
+                .err(),
+            Some(StatusCode::NotFound)
+        );
+    }
+
+    #[test]
+    fn route_with_catch_all() {
+        let mut router = Router::new();
+        router
+            .add(Method::Get, "/static/*path", |_| Response::new())
+            .add(Method::Get, "/static/index.html", |_| Response::new());
+
+        let (_, params) = router
+            .route(&parse("GET /static/css/app.css HTTP/1.1\r\n\r\n"))
+            .unwrap();
+        assert_eq!(params.get("path").map(String::as_str), Some("css/app.css"));
+
+        let (_, params) = router
+            .route(&parse("GET /static/index.html HTTP/1.1\r\n\r\n"))
+            .unwrap();
+        assert_eq!(params.get("path"), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn catch_all_must_be_last() {
+        Router::new().any("/static/*path/edit", |_| Response::new());
+    }
+
+    #[test]
+    fn options_lists_allowed_methods() {
+        let mut router = Router::new();
+        router
+            .add(Method::Get, "/items/:id", |_| Response::new())
+            .add(Method::Delete, "/items/:id", |_| Response::new())
+            .add(Method::Post, "/items", |_| Response::new());
+
+        let request = parse("OPTIONS /items/7 HTTP/1.1\r\n\r\n");
+        assert_eq!(
+            router.route(&request).err(),
+            Some(StatusCode::MethodNotAllowed)
+        );
+        let response = router.allow_response(&request);
+        assert_eq!(response.status_code(), StatusCode::NoContent);
+        assert_eq!(
+            response.header("Allow"),
+            Some("GET, DELETE, HEAD, OPTIONS")
+        );
+
+        let request = parse("PUT /items/7 HTTP/1.1\r\n\r\n");
+        let response = router.allow_response(&request);
+        assert_eq!(response.status_code(), StatusCode::MethodNotAllowed);
+        assert_eq!(
+            response.header("Allow"),
+            Some("GET, DELETE, HEAD, OPTIONS")
+        );
+    }
+
+    #[test]
+    fn head_falls_back_to_get() {
+        let mut router = Router::new();
+        router
+            .add(Method::Get, "/page", |_| Response::text("get"))
+            .add(Method::Get, "/both", |_| Response::text("get"))
+            .add(Method::Head, "/both", |_| Response::text("head"));
+
+        let request = parse("HEAD /page HTTP/1.1\r\n\r\n");
+        let Ok((Endpoint::Buffered(handler), _)) = router.route(&request)
+        else {
+            panic!("expected GET handler for HEAD");
+        };
+        assert!(handler(&request).serialise().ends_with("get"));
+
+        let request = parse("HEAD /both HTTP/1.1\r\n\r\n");
+        let Ok((Endpoint::Buffered(handler), _)) = router.route(&request)
+        else {
+            panic!("expected HEAD handler");
+        };
+        assert!(handler(&request).serialise().ends_with("head"));
+
+        assert_eq!(
+            router.route(&parse("POST /page HTTP/1.1\r\n\r\n")).err(),
+            Some(StatusCode::MethodNotAllowed)
+        );
+    }
+
+    #[test]
+    fn render_error_pages() {
+        let mut router = Router::new();
+        router
+            .add(Method::Get, "/gone", |_| {
+                Response::new().set_status_code(StatusCode::Forbidden)
+            })
+            .add(Method::Get, "/custom", |_| {
+                Response::new()
+                    .set_status_code(StatusCode::Forbidden)
+                    .set_body("bye")
+            })
+            .error_page(|_, response| response.set_body("rendered"));
+
+        let render = |raw: &str| {
+            let request = parse(raw);
+            let Ok((Endpoint::Buffered(handler), _)) = router.route(&request)
+            else {
+                panic!("expected handler");
+            };
+            router.run(&request, handler).serialise()
+        };
+        assert!(render("GET /gone HTTP/1.1\r\n\r\n").ends_with("rendered"));
+        assert!(render("GET /custom HTTP/1.1\r\n\r\n").ends_with("bye"));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    headers::Headers,
+    http::{Request, Response},
+    middleware::Next,
+};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    headers: Headers,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            headers: [
+                (
+                    "Strict-Transport-Security",
+                    "max-age=31536000; includeSubDomains",
+                ),
+                ("X-Content-Type-Options", "nosniff"),
+                ("X-Frame-Options", "DENY"),
+                ("Content-Security-Policy", "default-src 'self'"),
+                ("Referrer-Policy", "strict-origin-when-cross-origin"),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+impl SecurityHeaders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn remove(mut self, name: &str) -> Self {
+        self.headers.remove(name);
+        self
+    }
+
+    pub fn apply(&self, response: Response) -> Response {
+        self.headers
+            .iter()
+            .fold(response, |response, (name, value)| {
+                match response.header(name) {
+                    Some(_) => response,
+                    None => response.add_header(name, value),
+                }
+            })
+    }
+
+    pub fn middleware(
+        self,
+    ) -> impl Fn(&Request, Next) -> Response + Send + Sync + 'static {
+        let security = Arc::new(self);
+        move |request, next| security.apply(next.run(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_headers() {
+        let response = SecurityHeaders::new().apply(Response::new());
+        assert_eq!(
+            response.header("Strict-Transport-Security"),
+            Some("max-age=31536000; includeSubDomains")
+        );
+        assert_eq!(response.header("X-Content-Type-Options"), Some("nosniff"));
+        assert_eq!(response.header("X-Frame-Options"), Some("DENY"));
+        assert_eq!(
+            response.header("Content-Security-Policy"),
+            Some("default-src 'self'")
+        );
+        assert_eq!(
+            response.header("Referrer-Policy"),
+            Some("strict-origin-when-cross-origin")
+        );
+    }
+
+    #[test]
+    fn override_headers() {
+        let security = SecurityHeaders::new()
+            .set("X-Frame-Options", "SAMEORIGIN")
+            .remove("strict-transport-security");
+        let response = security.apply(
+            Response::new()
+                .add_header("Content-Security-Policy", "default-src *"),
+        );
+        assert_eq!(response.header("X-Frame-Options"), Some("SAMEORIGIN"));
+        assert_eq!(response.header("Strict-Transport-Security"), None);
+        assert_eq!(
+            response.header("Content-Security-Policy"),
+            Some("default-src *")
+        );
+    }
+}
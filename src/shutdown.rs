@@ -0,0 +1,132 @@
+use std::{
+    collections::HashMap,
+    net::{self, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    flag: Arc<AtomicBool>,
+    addr: SocketAddr,
+}
+
+impl ShutdownHandle {
+    pub(crate) fn new(mut addr: SocketAddr) -> Self {
+        if addr.ip().is_unspecified() {
+            addr.set_ip(match addr.ip() {
+                IpAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                IpAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        Self {
+            flag: Arc::new(AtomicBool::new(false)),
+            addr,
+        }
+    }
+
+    pub fn shutdown(&self) {
+        if !self.flag.swap(true, Ordering::SeqCst) {
+            // Wake the accept loop so it notices the flag.
+            let _ = TcpStream::connect(self.addr);
+        }
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Connections {
+    next: AtomicUsize,
+    streams: Mutex<HashMap<usize, Option<TcpStream>>>,
+    idle: Condvar,
+}
+
+impl Connections {
+    pub(crate) fn track(self: &Arc<Self>, stream: &TcpStream) -> Connection {
+        let id = self.next.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut streams) = self.streams.lock() {
+            streams.insert(id, stream.try_clone().ok());
+        }
+        Connection {
+            id,
+            connections: self.clone(),
+        }
+    }
+
+    pub(crate) fn drain(&self, timeout: Duration) {
+        let Ok(streams) = self.streams.lock() else {
+            return;
+        };
+        let Ok((streams, _)) =
+            self.idle.wait_timeout_while(streams, timeout, |streams| {
+                !streams.is_empty()
+            })
+        else {
+            return;
+        };
+        for stream in streams.values().flatten() {
+            let _ = stream.shutdown(net::Shutdown::Both);
+        }
+    }
+}
+
+pub(crate) struct Connection {
+    id: usize,
+    connections: Arc<Connections>,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Ok(mut streams) = self.connections.streams.lock() {
+            streams.remove(&self.id);
+        }
+        self.connections.idle.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Response, Server};
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn stop_accepting_and_drain() {
+        let server = Server::bind("127.0.0.1:0")
+            .unwrap()
+            .path("/", |_| Response::text("ok"))
+            .drain_timeout(Duration::from_millis(200));
+        let addr = server.local_addr().unwrap();
+        let handle = server.shutdown_handle();
+        let server = thread::spawn(move || server.listen());
+
+        let mut idle = TcpStream::connect(addr).unwrap();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !response.ends_with(b"ok") {
+            let len = stream.read(&mut buf).unwrap();
+            assert_ne!(len, 0);
+            response.extend_from_slice(&buf[..len]);
+        }
+
+        let start = Instant::now();
+        handle.shutdown();
+        server.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(handle.is_shutdown());
+        assert_eq!(idle.read(&mut buf).unwrap_or(0), 0);
+        assert!(TcpStream::connect(addr).is_err());
+    }
+}